@@ -2,27 +2,88 @@ use pyo3::prelude::*;
 
 pub mod deck;
 pub mod mana;
+pub mod mulligan;
+pub mod replay;
 pub mod sim;
+pub mod solver;
 pub mod stats;
+pub mod strategy;
 
-use crate::sim::run;
-use crate::stats::Stats;
+use crate::sim::{run, summarize};
+use crate::stats::{DeckSummary, Stats};
 use crate::deck::DeckFile;
+use crate::mulligan::KeepRuleKind;
+use crate::strategy::StrategyKind;
+use clap::ValueEnum;
 
-#[pyfunction]
-fn run_sim(deck_path: &str, sims: usize, turns: usize) -> PyResult<Stats> {
+fn load_deck(deck_path: &str) -> PyResult<DeckFile> {
     let f = std::fs::File::open(deck_path)
         .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
-    let deck: DeckFile =
-        serde_json::from_reader(f).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    serde_json::from_reader(f).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+fn parse_strategy(s: &str) -> PyResult<StrategyKind> {
+    StrategyKind::from_str(s, true).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+fn parse_keep_rule(s: &str) -> PyResult<KeepRuleKind> {
+    KeepRuleKind::from_str(s, true).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Returns (stats, seed) since `seed` is randomly chosen when omitted and the
+/// caller needs it back to reproduce the run (by passing it again as `seed`).
+#[pyfunction]
+#[pyo3(signature = (deck_path, sims, turns, seed=None, strategy="greedy", keep_rule="land-window", max_mulligans=3))]
+fn run_sim(
+    deck_path: &str,
+    sims: usize,
+    turns: usize,
+    seed: Option<u64>,
+    strategy: &str,
+    keep_rule: &str,
+    max_mulligans: usize,
+) -> PyResult<(Stats, u64)> {
+    let deck = load_deck(deck_path)?;
+    let strategy = parse_strategy(strategy)?;
+    let keep_rule = parse_keep_rule(keep_rule)?;
+
+    let seed = seed.unwrap_or_else(rand::random);
+    let stats = run(&deck, sims, turns, seed, strategy, keep_rule, max_mulligans, None);
+
+    Ok((stats, seed))
+}
+
+/// Runs the same sim over several decks under one shared seed/sim count and
+/// returns a comparison row per deck plus the seed used, for A/B testing deck edits.
+#[pyfunction]
+#[pyo3(signature = (deck_paths, sims, turns, seed=None, strategy="greedy", keep_rule="land-window", max_mulligans=3))]
+fn compare_decks(
+    deck_paths: Vec<String>,
+    sims: usize,
+    turns: usize,
+    seed: Option<u64>,
+    strategy: &str,
+    keep_rule: &str,
+    max_mulligans: usize,
+) -> PyResult<(Vec<DeckSummary>, u64)> {
+    let strategy = parse_strategy(strategy)?;
+    let keep_rule = parse_keep_rule(keep_rule)?;
+    let seed = seed.unwrap_or_else(rand::random);
 
-    let stats = run(&deck, sims, turns);
+    let rows = deck_paths
+        .into_iter()
+        .map(|path| {
+            let deck = load_deck(&path)?;
+            Ok(summarize(&deck, deck.name.clone(), sims, turns, seed, strategy, keep_rule, max_mulligans))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
 
-    Ok(stats)
+    Ok((rows, seed))
 }
 
 #[pymodule]
 fn mana_sim(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(run_sim, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_decks, m)?)?;
     Ok(())
 }
@@ -3,19 +3,33 @@ use std::path::PathBuf;
 
 mod deck;
 mod mana;
+mod mulligan;
+mod replay;
 mod sim;
+mod solver;
 mod stats;
+mod strategy;
 
 use deck::DeckFile;
-use sim::run;
+use mulligan::KeepRuleKind;
+use replay::GameReplay;
+use sim::{replay_game, run, summarize};
+use stats::DeckSummary;
+use strategy::StrategyKind;
 
 #[derive(Parser)]
 #[command(name = "mana_sim")]
 #[command(about = "MTG Commander mana simulation", long_about = None)]
 struct Args {
-    /// Deck JSON file path
+    /// Deck JSON file path (single-deck mode; ignored if --decks is given)
     #[arg(short, long)]
-    deck: PathBuf,
+    deck: Option<PathBuf>,
+
+    /// Compare multiple decks under the same seed/sim count instead of running
+    /// one: each path may be a deck JSON file or a directory, which is expanded
+    /// to every *.json file inside it
+    #[arg(long, num_args = 1.., value_delimiter = ',')]
+    decks: Option<Vec<PathBuf>>,
 
     /// Number of simulations to run
     #[arg(short, long, default_value = "50000")]
@@ -32,75 +46,236 @@ struct Args {
     /// Print example game traces
     #[arg(short, long)]
     verbose: bool,
+
+    /// Base RNG seed; each simulated game derives its own seed from this so the
+    /// whole run is reproducible regardless of thread scheduling. Random if omitted.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Casting strategy the pilot uses to decide which castable cards to play
+    #[arg(short = 'g', long = "strategy", value_enum, default_value = "greedy")]
+    strategy: StrategyKind,
+
+    /// Keep/mulligan rule applied to each opening hand
+    #[arg(long, value_enum, default_value = "land-window")]
+    keep_rule: KeepRuleKind,
+
+    /// Maximum number of London mulligans taken before the hand is kept regardless
+    #[arg(long, default_value = "3")]
+    max_mulligans: usize,
+
+    /// Write one JSON replay file per simulated game into this directory
+    #[arg(long)]
+    replays: Option<PathBuf>,
+
+    /// Instead of running a batch, re-run exactly the recorded game in this
+    /// replay file and print its turn-by-turn trace
+    #[arg(long)]
+    replay: Option<PathBuf>,
+}
+
+fn print_replay(replay: &GameReplay) {
+    println!(
+        "Replaying seed {} (strategy {:?}) over {} turns\n",
+        replay.seed,
+        replay.strategy,
+        replay.turns.len()
+    );
+    for turn in &replay.turns {
+        println!(
+            "Turn {}: {} cards cast, {}/{} mana used, classified {}",
+            turn.turn,
+            turn.cards_cast.len(),
+            turn.mana_spent,
+            turn.mana_produced,
+            turn.classification
+        );
+        if let Some(drew) = &turn.drew {
+            println!("  Drew: {:?}", drew);
+        }
+        if let Some(land) = &turn.land_played {
+            println!("  Played land: {:?}", land);
+        }
+        for cast in &turn.cards_cast {
+            println!("  Cast {} (generic {}, pips {:?})", cast.kind, cast.generic, cast.pips);
+        }
+    }
+}
+
+/// Expands each path into one or more deck JSON files: directories are
+/// expanded to their `*.json` children (sorted for a stable table order),
+/// plain files are passed through as-is.
+fn expand_deck_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut children: Vec<PathBuf> = std::fs::read_dir(path)
+                .unwrap_or_else(|e| panic!("Failed to read directory {}: {}", path.display(), e))
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            children.sort();
+            out.extend(children);
+        } else {
+            out.push(path.clone());
+        }
+    }
+    out
+}
+
+fn load_deck(path: &PathBuf) -> DeckFile {
+    let file = std::fs::File::open(path)
+        .unwrap_or_else(|e| panic!("Failed to open deck file {}: {}", path.display(), e));
+    serde_json::from_reader(file)
+        .unwrap_or_else(|e| panic!("Failed to parse deck JSON {}: {}", path.display(), e))
+}
+
+/// Runs every deck in `paths` under the same seed/sim count/turns and prints
+/// an aligned comparison table, for A/B testing deck edits against each other.
+fn run_comparison(args: &Args, paths: &[PathBuf]) {
+    let deck_paths = expand_deck_paths(paths);
+    let seed = args.seed.unwrap_or_else(rand::random);
+
+    println!("Comparing {} decks over {} simulations, {} turns (seed {})...",
+        deck_paths.len(), args.sims, args.turns, seed);
+
+    let rows: Vec<DeckSummary> = deck_paths
+        .iter()
+        .map(|path| {
+            let deck = load_deck(path);
+            let name = deck.name.clone();
+            summarize(&deck, name, args.sims, args.turns, seed, args.strategy, args.keep_rule, args.max_mulligans)
+        })
+        .collect();
+
+    let name_width = rows.iter().map(|r| r.name.len()).max().unwrap_or(4).max(4);
+    println!(
+        "\n{:<name_width$}  {:>7}  {:>7}  {:>7}  {:>11}  {:>9}",
+        "Deck", "Screw%", "Flood%", "Normal%", "Efficiency%", "AvgCast",
+        name_width = name_width
+    );
+    for row in &rows {
+        println!(
+            "{:<name_width$}  {:>7.1}  {:>7.1}  {:>7.1}  {:>11.1}  {:>9.2}",
+            row.name, row.screw_rate, row.flood_rate, row.ok_rate, row.mana_efficiency, row.avg_cards_cast,
+            name_width = name_width
+        );
+    }
+
+    if let Some(output_path) = &args.output {
+        let is_csv = output_path.extension().is_some_and(|ext| ext == "csv");
+        if is_csv {
+            let mut csv = String::from("deck,screw_rate,flood_rate,ok_rate,mana_efficiency,avg_cards_cast\n");
+            for row in &rows {
+                csv.push_str(&format!(
+                    "{},{:.2},{:.2},{:.2},{:.2},{:.2}\n",
+                    row.name, row.screw_rate, row.flood_rate, row.ok_rate, row.mana_efficiency, row.avg_cards_cast
+                ));
+            }
+            std::fs::write(output_path, csv)
+                .unwrap_or_else(|e| panic!("Failed to write output: {}", e));
+        } else {
+            let json: Vec<_> = rows
+                .iter()
+                .map(|row| {
+                    serde_json::json!({
+                        "deck": row.name,
+                        "screw_rate": row.screw_rate,
+                        "flood_rate": row.flood_rate,
+                        "ok_rate": row.ok_rate,
+                        "mana_efficiency": row.mana_efficiency,
+                        "avg_cards_cast": row.avg_cards_cast,
+                    })
+                })
+                .collect();
+            std::fs::write(output_path, serde_json::to_string_pretty(&json).unwrap())
+                .unwrap_or_else(|e| panic!("Failed to write output: {}", e));
+        }
+        println!("\nOutput written to: {}", output_path.display());
+    }
 }
 
 fn main() {
     let args = Args::parse();
 
-    println!("Loading deck: {}", args.deck.display());
-    let file = std::fs::File::open(&args.deck)
-        .unwrap_or_else(|e| panic!("Failed to open deck file: {}", e));
-    
-    let deck: DeckFile = serde_json::from_reader(file)
-        .unwrap_or_else(|e| panic!("Failed to parse deck JSON: {}", e));
+    if let Some(deck_paths) = &args.decks {
+        run_comparison(&args, deck_paths);
+        return;
+    }
+
+    let deck_path = args.deck.clone().unwrap_or_else(|| panic!("--deck is required unless --decks is given"));
+
+    println!("Loading deck: {}", deck_path.display());
+    let deck = load_deck(&deck_path);
+
+    if let Some(replay_path) = args.replay {
+        let replay = GameReplay::load(&replay_path)
+            .unwrap_or_else(|e| panic!("Failed to load replay {}: {}", replay_path.display(), e));
+        let rerun = replay_game(
+            &deck,
+            &replay.deck_name,
+            replay.turns.len(),
+            replay.seed,
+            replay.strategy,
+            replay.keep_rule,
+            replay.max_mulligans,
+        )
+        .unwrap_or_else(|e| panic!("{}", e));
+        print_replay(&rerun);
+        return;
+    }
+
+    let seed = args.seed.unwrap_or_else(rand::random);
 
     println!("Commander: {}", deck.name);
-    println!("Running {} simulations over {} turns...", args.sims, args.turns);
+    println!("Running {} simulations over {} turns (seed {})...", args.sims, args.turns, seed);
     let start = std::time::Instant::now();
-    let stats = run(&deck, args.sims, args.turns);
+    let stats = run(
+        &deck,
+        args.sims,
+        args.turns,
+        seed,
+        args.strategy,
+        args.keep_rule,
+        args.max_mulligans,
+        args.replays.as_deref(),
+    );
     let elapsed = start.elapsed();
 
     println!("\nCompleted in {:.2}s\n", elapsed.as_secs_f64());
 
     println!("=== Mana Analysis ===");
-    
+
     // Calculate overall statistics
     let avg_screw = stats.screw.iter().sum::<f64>() / stats.screw.len() as f64;
     let avg_flood = stats.flood.iter().sum::<f64>() / stats.flood.len() as f64;
     let avg_ok = stats.ok.iter().sum::<f64>() / stats.ok.len() as f64;
-    let avg_cards_cast = stats.avg_cards_cast.iter().sum::<f64>() / stats.avg_cards_cast.len() as f64;
-    let avg_mana_avail = stats.avg_mana_available.iter().sum::<f64>() / stats.avg_mana_available.len() as f64;
-    let avg_mana_spent = stats.avg_mana_spent.iter().sum::<f64>() / stats.avg_mana_spent.len() as f64;
-    let efficiency = if avg_mana_avail > 0.0 { avg_mana_spent / avg_mana_avail * 100.0 } else { 0.0 };
 
     println!("Screw Rate:  {:.1}%", avg_screw * 100.0);
     println!("Flood Rate:  {:.1}%", avg_flood * 100.0);
     println!("Normal Rate: {:.1}%", avg_ok * 100.0);
-    println!("\nAverage cards cast per turn: {:.2}", avg_cards_cast);
-    println!("Mana efficiency: {:.1}%", efficiency);
-
-    // Print example traces if verbose
-    if args.verbose && !stats.example_traces.is_empty() {
-        println!("\n=== Example Game Traces ===\n");
-        for (i, trace) in stats.example_traces.iter().enumerate() {
-            println!("Game {} - Final Status: {}", i + 1, trace.final_status);
-            for snap in &trace.turns {
-                println!("  Turn {}: {} cards cast, {}/{} mana, {} cards in hand [{}]",
-                    snap.turn, snap.cards_cast, snap.mana_spent, snap.mana_available,
-                    snap.hand.len(), snap.status);
-                if !snap.played_cards.is_empty() {
-                    println!("    Played: {}", snap.played_cards.join(", "));
-                }
-                if !snap.hand.is_empty() {
-                    println!("    Hand: {}", snap.hand.join(", "));
-                }
-            }
-            println!();
-        }
+    println!("\nAverage cards cast per game: {:.2}", stats.avg_cards_cast);
+    println!("Mana efficiency: {:.1}%", stats.mana_efficiency);
+
+    println!("\nMulligans taken:");
+    for (count, fraction) in stats.mulligan_distribution.iter().enumerate() {
+        println!("  {}: {:.1}%", count, fraction * 100.0);
+    }
+
+    if args.verbose {
+        println!("\n(--verbose has no effect yet: per-game trace capture isn't implemented in this build.)");
     }
-    
+
     if let Some(output_path) = args.output {
         let json = serde_json::json!({
             "screw": stats.screw,
             "flood": stats.flood,
             "ok": stats.ok,
-            "avg_mana_spent": stats.avg_mana_spent,
-            "avg_mana_available": stats.avg_mana_available,
             "avg_cards_cast": stats.avg_cards_cast,
-            "avg_hand_size": stats.avg_hand_size,
-            "example_traces": stats.example_traces
+            "mana_efficiency": stats.mana_efficiency,
+            "mulligan_distribution": stats.mulligan_distribution
         });
-        
+
         std::fs::write(&output_path, serde_json::to_string_pretty(&json).unwrap())
             .unwrap_or_else(|e| panic!("Failed to write output: {}", e));
         println!("Output written to: {}", output_path.display());
@@ -23,3 +23,108 @@ pub fn hand_cost(hand: &[Card]) -> (u32, HashMap<String, u32>) {
 
     (generic, colored)
 }
+
+/// Mana pool with all colors and generic
+#[derive(Clone)]
+pub struct ManaPool {
+    pub(crate) generic: u32,
+    pub(crate) colors: HashMap<String, u32>,
+}
+
+impl ManaPool {
+    pub fn new() -> Self {
+        let mut colors = HashMap::new();
+        for c in &["W", "U", "B", "R", "G", "C"] {
+            colors.insert(c.to_string(), 0);
+        }
+        ManaPool { generic: 0, colors }
+    }
+
+    pub fn add_color(&mut self, c: &str, n: u32) {
+        *self.colors.entry(c.to_string()).or_insert(0) += n;
+    }
+
+    pub fn can_pay(&self, generic: u32, pips: &[String]) -> bool {
+        // Count required pips per color
+        let mut required_colors: HashMap<String, u32> = HashMap::new();
+        for p in pips {
+            *required_colors.entry(p.clone()).or_insert(0) += 1;
+        }
+
+        // Check we have enough of each color
+        for (color, needed) in &required_colors {
+            if self.colors.get(color).copied().unwrap_or(0) < *needed {
+                return false;
+            }
+        }
+
+        // Calculate total available mana after colored requirements
+        let mut remaining_mana = self.generic;
+        for (color, count) in &self.colors {
+            let used = required_colors.get(color).copied().unwrap_or(0);
+            remaining_mana += count.saturating_sub(used);
+        }
+
+        remaining_mana >= generic
+    }
+
+    pub fn spend_safe(&mut self, generic: u32, pips: &[String]) -> bool {
+        if !self.can_pay(generic, pips) {
+            return false;
+        }
+
+        // Count and pay colored pips
+        let mut pip_counts: HashMap<String, u32> = HashMap::new();
+        for p in pips {
+            *pip_counts.entry(p.clone()).or_insert(0) += 1;
+        }
+
+        for (color, count) in pip_counts {
+            *self.colors.get_mut(&color).unwrap() -= count;
+        }
+
+        // Pay generic cost with remaining mana
+        let mut remaining = generic;
+
+        // Use generic mana first
+        let from_generic = remaining.min(self.generic);
+        self.generic -= from_generic;
+        remaining -= from_generic;
+
+        // Use colored mana for generic cost if needed
+        if remaining > 0 {
+            for color_count in self.colors.values_mut() {
+                if remaining == 0 {
+                    break;
+                }
+                let from_color = remaining.min(*color_count);
+                *color_count -= from_color;
+                remaining -= from_color;
+            }
+        }
+
+        true
+    }
+
+    /// Total mana left in the pool across generic and all colors.
+    pub fn total(&self) -> u32 {
+        self.generic + self.colors.values().sum::<u32>()
+    }
+
+    /// Deducts an exact spend, e.g. one computed by `solver::allocate`, rather
+    /// than re-deriving it from a cost via `spend_safe`.
+    pub fn spend_exact(&mut self, colored: &HashMap<String, u32>, generic: u32) {
+        for (c, n) in colored {
+            if let Some(v) = self.colors.get_mut(c) {
+                *v = v.saturating_sub(*n);
+            }
+        }
+        self.generic = self.generic.saturating_sub(generic);
+    }
+}
+
+impl Default for ManaPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,171 @@
+use crate::deck::Card;
+use crate::strategy::mana_value;
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+/// Decides whether a freshly drawn 7-card hand is a keep, given how many
+/// mulligans have already been taken this game.
+pub trait KeepPolicy {
+    fn should_keep(&self, hand: &[Card], mulligans_taken: usize) -> bool;
+}
+
+/// Keeps any hand with 2-5 lands, mirroring the original fixed heuristic.
+pub struct LandWindowPolicy {
+    pub min_lands: usize,
+    pub max_lands: usize,
+}
+
+impl Default for LandWindowPolicy {
+    fn default() -> Self {
+        Self { min_lands: 2, max_lands: 5 }
+    }
+}
+
+impl KeepPolicy for LandWindowPolicy {
+    fn should_keep(&self, hand: &[Card], _mulligans_taken: usize) -> bool {
+        let land_count = hand.iter().filter(|c| matches!(c, Card::Land { .. })).count();
+        land_count >= self.min_lands && land_count <= self.max_lands
+    }
+}
+
+/// Keeps any hand in the land window that also holds a non-land card it could
+/// plausibly cast early, rather than just counting lands.
+pub struct CastableEarlyPlayPolicy {
+    pub min_lands: usize,
+    pub max_lands: usize,
+}
+
+impl Default for CastableEarlyPlayPolicy {
+    fn default() -> Self {
+        Self { min_lands: 2, max_lands: 5 }
+    }
+}
+
+impl KeepPolicy for CastableEarlyPlayPolicy {
+    fn should_keep(&self, hand: &[Card], _mulligans_taken: usize) -> bool {
+        let land_count = hand.iter().filter(|c| matches!(c, Card::Land { .. })).count();
+        if land_count < self.min_lands || land_count > self.max_lands {
+            return false;
+        }
+
+        hand.iter()
+            .filter(|c| !matches!(c, Card::Land { .. }))
+            .any(|c| mana_value(c) as usize <= land_count.max(1))
+    }
+}
+
+/// Which `KeepPolicy` to mulligan with, selected via `--keep-rule`.
+#[derive(Clone, Copy, Debug, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeepRuleKind {
+    LandWindow,
+    CastableEarly,
+}
+
+impl KeepRuleKind {
+    pub fn build(self) -> Box<dyn KeepPolicy> {
+        match self {
+            KeepRuleKind::LandWindow => Box::new(LandWindowPolicy::default()),
+            KeepRuleKind::CastableEarly => Box::new(CastableEarlyPlayPolicy::default()),
+        }
+    }
+}
+
+/// Runs the London mulligan: repeatedly draw 7, decide keep/mulligan via
+/// `policy`, and on the Nth kept mulligan bottom N cards of choice back onto
+/// the deck before the game proceeds. A hand is forced to keep once
+/// `max_mulligans` have already been taken. Returns the kept hand and how
+/// many mulligans it took.
+pub fn run_mulligan(
+    draw_deck: &mut Vec<Card>,
+    rng: &mut StdRng,
+    policy: &dyn KeepPolicy,
+    max_mulligans: usize,
+) -> (Vec<Card>, usize) {
+    let mut mulligans_taken = 0;
+
+    loop {
+        let mut hand = Vec::with_capacity(7);
+        for _ in 0..7 {
+            hand.push(draw_deck.pop().unwrap());
+        }
+
+        if mulligans_taken >= max_mulligans || policy.should_keep(&hand, mulligans_taken) {
+            bottom_worst(&mut hand, draw_deck, mulligans_taken);
+            return (hand, mulligans_taken);
+        }
+
+        draw_deck.extend(hand);
+        draw_deck.shuffle(rng);
+        mulligans_taken += 1;
+    }
+}
+
+/// Bottoms `n` cards of choice: the most expensive non-land cards first,
+/// since those are hardest to cast off a shrunken hand, and lands only once
+/// nothing else is left. Lands are never bottomed ahead of a non-land card,
+/// even in a hand kept by force after `max_mulligans` with more lands than
+/// the keep policy's window allows.
+fn bottom_worst(hand: &mut Vec<Card>, draw_deck: &mut Vec<Card>, n: usize) {
+    let keep_score = |card: &Card| -> i64 {
+        match card {
+            Card::Land { .. } => i64::MAX,
+            other => -(mana_value(other) as i64),
+        }
+    };
+
+    for _ in 0..n.min(hand.len()) {
+        let worst = (0..hand.len())
+            .min_by_key(|&i| keep_score(&hand[i]))
+            .expect("hand is non-empty");
+        draw_deck.insert(0, hand.remove(worst));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    struct NeverKeepPolicy;
+
+    impl KeepPolicy for NeverKeepPolicy {
+        fn should_keep(&self, _hand: &[Card], _mulligans_taken: usize) -> bool {
+            false
+        }
+    }
+
+    fn test_deck(n: usize) -> Vec<Card> {
+        (0..n).map(|_| Card::Land { produces: vec!["C".to_string()], count: 1 }).collect()
+    }
+
+    #[test]
+    fn forced_keep_stops_at_max_mulligans() {
+        let mut draw_deck = test_deck(20);
+        let mut rng = StdRng::seed_from_u64(1);
+        let policy = NeverKeepPolicy;
+
+        let (hand, mulligans_taken) = run_mulligan(&mut draw_deck, &mut rng, &policy, 3);
+
+        assert_eq!(mulligans_taken, 3);
+        // A kept hand after `n` mulligans bottoms `n` cards of choice.
+        assert_eq!(hand.len(), 7 - 3);
+    }
+
+    #[test]
+    fn bottom_count_matches_mulligans_taken() {
+        let total = 20;
+        let mut draw_deck = test_deck(total);
+        let mut rng = StdRng::seed_from_u64(7);
+        let policy = NeverKeepPolicy;
+
+        let (hand, mulligans_taken) = run_mulligan(&mut draw_deck, &mut rng, &policy, 2);
+
+        // No cards are created or destroyed: whatever isn't in the kept hand
+        // (after bottoming) must be back in the draw deck.
+        assert_eq!(hand.len() + draw_deck.len(), total);
+        assert_eq!(hand.len(), 7 - mulligans_taken);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::deck::Card;
+use crate::mulligan::KeepRuleKind;
+use crate::strategy::StrategyKind;
+use serde::{Deserialize, Serialize};
+
+/// A single cast recorded during a turn, with the cost actually paid.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CastRecord {
+    pub kind: String,
+    pub generic: u32,
+    pub pips: Vec<String>,
+}
+
+/// Everything that happened on one turn of a recorded game.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TurnRecord {
+    pub turn: usize,
+    pub drew: Option<Card>,
+    pub land_played: Option<Card>,
+    pub cards_cast: Vec<CastRecord>,
+    pub mana_produced: u32,
+    pub mana_leftover: u32,
+    /// `mana_produced - mana_leftover`, floored at zero: ramp/fetch can
+    /// net-increase the pool within a turn, so the subtraction saturates rather
+    /// than underflows. Mana that ramp/fetch added mid-turn is not counted as
+    /// produced, so on such turns this can be less than the total cost paid.
+    pub mana_spent: u32,
+    pub classification: String,
+}
+
+/// A full machine-readable log of one simulated game: enough to reproduce it
+/// exactly (`seed` + `strategy`) and to inspect why it was classified the way
+/// it was without re-deriving that from the aggregate stats.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameReplay {
+    /// The deck this game was simulated from (`DeckFile::name`), so `--replay`
+    /// can refuse to reproduce a game against a different deck.
+    pub deck_name: String,
+    pub seed: u64,
+    pub strategy: StrategyKind,
+    pub keep_rule: KeepRuleKind,
+    pub max_mulligans: usize,
+    pub mulligans_taken: usize,
+    pub deck_order: Vec<Card>,
+    pub turns: Vec<TurnRecord>,
+}
+
+impl GameReplay {
+    /// Writes this replay as `<dir>/game_<seed>.json` with the seed as 16
+    /// zero-padded lowercase hex digits, creating `dir` if needed.
+    pub fn write_to_dir(
+        &self,
+        dir: &std::path::Path,
+    ) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("game_{:016x}.json", self.seed));
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &std::path::Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+}
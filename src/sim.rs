@@ -1,248 +1,362 @@
 use crate::deck::{Card, DeckFile};
-use crate::stats::Stats;
+use crate::mana::ManaPool;
+use crate::mulligan::{self, KeepRuleKind};
+use crate::replay::{CastRecord, GameReplay, TurnRecord};
+use crate::solver;
+use crate::stats::{DeckSummary, Stats};
+use crate::strategy::{CastStrategy, StrategyKind};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::SeedableRng;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::path::Path;
 
-/// Mana pool with all colors and generic
-#[derive(Clone)]
-struct ManaPool {
-    generic: u32,
-    colors: HashMap<String, u32>,
-}
-
-impl ManaPool {
-    fn new() -> Self {
-        let mut colors = HashMap::new();
-        for c in &["W", "U", "B", "R", "G", "C"] {
-            colors.insert(c.to_string(), 0);
-        }
-        ManaPool { generic: 0, colors }
-    }
-
-    fn add_color(&mut self, c: &str, n: u32) {
-        *self.colors.entry(c.to_string()).or_insert(0) += n;
-    }
-
-    fn can_pay(&self, generic: u32, pips: &[String]) -> bool {
-        // Count required pips per color
-        let mut required_colors: HashMap<String, u32> = HashMap::new();
-        for p in pips {
-            *required_colors.entry(p.clone()).or_insert(0) += 1;
-        }
-        
-        // Check we have enough of each color
-        for (color, needed) in &required_colors {
-            if self.colors.get(color).copied().unwrap_or(0) < *needed {
-                return false;
-            }
-        }
-        
-        // Calculate total available mana after colored requirements
-        let mut remaining_mana = self.generic;
-        for (color, count) in &self.colors {
-            let used = required_colors.get(color).copied().unwrap_or(0);
-            remaining_mana += count.saturating_sub(used);
-        }
-        
-        remaining_mana >= generic
-    }
-
-    fn spend_safe(&mut self, generic: u32, pips: &[String]) -> bool {
-        if !self.can_pay(generic, pips) {
-            return false;
-        }
-        
-        // Count and pay colored pips
-        let mut pip_counts: HashMap<String, u32> = HashMap::new();
-        for p in pips {
-            *pip_counts.entry(p.clone()).or_insert(0) += 1;
-        }
-        
-        for (color, count) in pip_counts {
-            *self.colors.get_mut(&color).unwrap() -= count;
-        }
-        
-        // Pay generic cost with remaining mana
-        let mut remaining = generic;
-        
-        // Use generic mana first
-        let from_generic = remaining.min(self.generic);
-        self.generic -= from_generic;
-        remaining -= from_generic;
-        
-        // Use colored mana for generic cost if needed
-        if remaining > 0 {
-            for color_count in self.colors.values_mut() {
-                if remaining == 0 {
-                    break;
-                }
-                let from_color = remaining.min(*color_count);
-                *color_count -= from_color;
-                remaining -= from_color;
-            }
-        }
-        
-        true
-    }
-}
-
-/// Attempts to cast cards, returns true if any card was cast
+/// Attempts to cast cards, returns whether any card was cast and a log of what
+/// was cast (used for replay export; cheap enough to always build).
 /// add power, toughness, etc output for tracking how we're doing
 /// eg. a lot of artifact equipments = no actual damage
 /// but a lot of little creatures is a lot of power. add some counters, equipment, etc... it increases,
-/// how do we track that? or at least attempt to? 
-fn play_cards(hand: &mut Vec<Card>, mana: &mut ManaPool, commander: &mut Option<Card>) -> bool {
+/// how do we track that? or at least attempt to?
+fn play_cards(
+    hand: &mut Vec<Card>,
+    mana: &mut ManaPool,
+    commander: &mut Option<Card>,
+    strategy: &dyn CastStrategy,
+) -> (bool, Vec<CastRecord>) {
     let mut cast_any = false;
+    let mut cast_log = Vec::new();
 
+    // Ramp/fetch unlock more mana, so they're cast greedily and immediately,
+    // one at a time, since casting one can make the next affordable.
     loop {
         let mut played = false;
 
-        // Cast ramp/fetch first (they produce mana)
-        for i in (0..hand.len()).rev() {
-            match &hand[i] {
+        for i in strategy.choose_plays(hand, mana, commander) {
+            if i >= hand.len() {
+                continue;
+            }
+
+            let produces = match &hand[i] {
                 Card::Ramp { generic, produces, .. } => {
-                    if mana.spend_safe(*generic as u32, &[]) {
-                        for c in produces {
-                            if c == "C" {
-                                mana.generic += 1;
-                            } else {
-                                mana.add_color(c, 1);
-                            }
-                        }
-                        hand.remove(i);
-                        cast_any = true;
-                        played = true;
-                    }
+                    mana.spend_safe(*generic as u32, &[]).then(|| (produces.clone(), *generic as u32))
                 }
                 Card::Fetch { generic, fetches, .. } => {
-                    if mana.spend_safe(*generic as u32, &[]) {
-                        for c in fetches {
-                            if c == "C" {
-                                mana.generic += 1;
-                            } else {
-                                mana.add_color(c, 1);
-                            }
-                        }
-                        hand.remove(i);
-                        cast_any = true;
-                        played = true;
+                    mana.spend_safe(*generic as u32, &[]).then(|| (fetches.clone(), *generic as u32))
+                }
+                _ => None,
+            };
+
+            if let Some((produces, generic)) = produces {
+                for c in &produces {
+                    if c == "C" {
+                        mana.generic += 1;
+                    } else {
+                        mana.add_color(c, 1);
                     }
                 }
-                _ => {}
+                let kind = if matches!(hand[i], Card::Ramp { .. }) { "ramp" } else { "fetch" };
+                cast_log.push(CastRecord { kind: kind.to_string(), generic, pips: Vec::new() });
+                hand.remove(i);
+                cast_any = true;
+                played = true;
+                break;
             }
         }
 
-        // Cast spells
-        for i in (0..hand.len()).rev() {
-            if let Card::Spell { generic, pips, .. } = &hand[i] {
-                if mana.spend_safe(*generic as u32, pips) {
-                    hand.remove(i);
-                    cast_any = true;
-                    played = true;
-                }
-            }
+        if !played {
+            break;
         }
+    }
 
-        // Cast commander if possible
-        if let Some(cmd) = commander {
-            if let Card::Commander { generic, pips, .. } = cmd {
-                if mana.spend_safe(*generic as u32, pips) {
-                    *commander = None;
-                    cast_any = true;
-                    played = true;
-                }
+    // Once mana production has settled, decide a jointly-castable set of spells
+    // with the max-flow solver instead of paying for them one at a time, since
+    // that can strand a color a later, higher-priority spell needs.
+    // `solver::allocate` tries a few heuristic orderings and keeps the largest
+    // it finds; it is not an exhaustive search, so it isn't guaranteed to find
+    // the true maximum jointly-castable set.
+    let spell_order: Vec<usize> = strategy
+        .choose_plays(hand, mana, commander)
+        .into_iter()
+        .filter(|&i| matches!(hand[i], Card::Spell { .. }))
+        .collect();
+    let allocation = solver::allocate(mana, hand, &spell_order);
+    if !allocation.cast.is_empty() {
+        mana.spend_exact(&allocation.colored_spend, allocation.generic_spend);
+        let mut cast_indices = allocation.cast;
+        cast_indices.sort_unstable_by(|a, b| b.cmp(a));
+        for i in cast_indices {
+            if let Card::Spell { generic, pips, .. } = hand.remove(i) {
+                cast_log.push(CastRecord { kind: "spell".to_string(), generic: generic as u32, pips });
             }
         }
+        cast_any = true;
+    }
 
-        if !played {
-            break;
+    // Cast the commander last, from whatever mana is left over
+    if let Some(cmd) = commander {
+        if let Card::Commander { generic, pips, .. } = cmd {
+            if mana.spend_safe(*generic as u32, pips) {
+                cast_log.push(CastRecord {
+                    kind: "commander".to_string(),
+                    generic: *generic as u32,
+                    pips: pips.clone(),
+                });
+                *commander = None;
+                cast_any = true;
+            }
         }
     }
 
-    cast_any
+    (cast_any, cast_log)
 }
 
-pub fn run(deck_file: &DeckFile, sims: usize, turns: usize) -> Stats {
-    let deck = deck_file.expand();
-    let commander_card = deck_file.commander();
+/// Per-turn screw/flood/ok tallies for one simulated game, plus its full replay
+/// record when `record` was requested.
+struct GameResult {
+    per_turn: Vec<(u32, u32, u32)>,
+    mulligans_taken: usize,
+    cards_cast: usize,
+    mana_produced: u64,
+    mana_spent: u64,
+    replay: Option<GameReplay>,
+}
 
-    let results: Vec<Vec<(u32, u32, u32)>> = (0..sims)
-        .into_par_iter()
-        .map(|_| {
-            let mut rng = thread_rng();
-            let mut draw_deck = deck.clone();
-            draw_deck.shuffle(&mut rng);
-
-            let mut hand: Vec<Card> = Vec::new();
-            let mut battlefield: Vec<Card> = Vec::new();
-            let mut commander = Some(commander_card.clone());
-
-            let mut screw = vec![0u32; turns];
-            let mut flood = vec![0u32; turns];
-            let mut ok = vec![0u32; turns];
-
-            for turn in 0..turns {
-                // Draw cards
-                if turn == 0 {
-                    for _ in 0..7 {
-                        hand.push(draw_deck.pop().unwrap());
-                    }
-                    
-                    // Mulligan logic: keep if 2-5 lands
-                    let land_count = hand.iter().filter(|c| matches!(c, Card::Land { .. })).count();
-                    if land_count < 2 || land_count > 5 {
-                        draw_deck.extend(hand.drain(..));
-                        draw_deck.shuffle(&mut rng);
-                        for _ in 0..7 {
-                            hand.push(draw_deck.pop().unwrap());
-                        }
-                    }
-                } else if let Some(c) = draw_deck.pop() {
-                    hand.push(c);
-                }
+/// Simulates one game deterministically from `seed`, optionally building a full
+/// `GameReplay` so both the aggregate `run` and the single-game `replay_game`
+/// entry points share one implementation.
+#[allow(clippy::too_many_arguments)]
+fn simulate_game(
+    deck_name: &str,
+    deck: &[Card],
+    commander_card: &Card,
+    turns: usize,
+    seed: u64,
+    strategy_kind: StrategyKind,
+    keep_rule: KeepRuleKind,
+    max_mulligans: usize,
+    record: bool,
+) -> GameResult {
+    let strategy = strategy_kind.build();
+    let keep_policy = keep_rule.build();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut draw_deck = deck.to_vec();
+    draw_deck.shuffle(&mut rng);
 
-                // Play one land automatically
-                if let Some(pos) = hand.iter().position(|c| matches!(c, Card::Land { .. })) {
-                    battlefield.push(hand.remove(pos));
-                }
+    let (mut hand, mulligans_taken) =
+        mulligan::run_mulligan(&mut draw_deck, &mut rng, keep_policy.as_ref(), max_mulligans);
+
+    // Snapshotted after mulligans settle, not right after the initial shuffle:
+    // `run_mulligan` reshuffles `draw_deck` on every rejected hand, so an
+    // earlier snapshot would disagree with what the recorded turns actually drew.
+    let deck_order = if record { draw_deck.clone() } else { Vec::new() };
+    let mut battlefield: Vec<Card> = Vec::new();
+    let mut commander = Some(commander_card.clone());
+
+    let mut screw = vec![0u32; turns];
+    let mut flood = vec![0u32; turns];
+    let mut ok = vec![0u32; turns];
+    let mut turn_records = Vec::new();
+    let mut cards_cast = 0usize;
+    let mut mana_produced_total = 0u64;
+    let mut mana_spent_total = 0u64;
 
-                // Build mana pool from lands only
-                let mut mana = ManaPool::new();
-                for l in &battlefield {
-                    if let Card::Land { produces, .. } = l {
-                        for c in produces {
-                            if c == "C" {
-                                mana.generic += 1;
-                            } else {
-                                mana.add_color(c, 1);
-                            }
-                        }
+    for turn in 0..turns {
+        // Draw a card every turn after the opening hand
+        let drew = if turn == 0 {
+            None
+        } else if let Some(c) = draw_deck.pop() {
+            hand.push(c.clone());
+            Some(c)
+        } else {
+            None
+        };
+
+        // Play one land automatically
+        let land_played = hand
+            .iter()
+            .position(|c| matches!(c, Card::Land { .. }))
+            .map(|pos| hand.remove(pos));
+        if let Some(land) = &land_played {
+            battlefield.push(land.clone());
+        }
+
+        // Build mana pool from lands only
+        let mut mana = ManaPool::new();
+        for l in &battlefield {
+            if let Card::Land { produces, .. } = l {
+                for c in produces {
+                    if c == "C" {
+                        mana.generic += 1;
+                    } else {
+                        mana.add_color(c, 1);
                     }
                 }
+            }
+        }
+
+        // Let the strategy see what's coming off the top of the deck next turn
+        strategy.peek(draw_deck.last());
+
+        let mana_produced = mana.total();
+
+        // Play cards, add power, toughness, creatures, etc stats counter to track play performance
+        let (cast_any, cast_log) = play_cards(&mut hand, &mut mana, &mut commander, strategy.as_ref());
+
+        // Calculate total leftover mana
+        let leftover_mana = mana.total();
+
+        // Ramp/fetch can net-increase the pool within a turn (e.g. a ritual that
+        // produces more mana than it costs), so `leftover_mana` is not guaranteed
+        // to be <= `mana_produced`; saturate rather than underflow.
+        let mana_spent = mana_produced.saturating_sub(leftover_mana);
+
+        cards_cast += cast_log.len();
+        mana_produced_total += mana_produced as u64;
+        mana_spent_total += mana_spent as u64;
+
+        // Classify turn
+        let classification = if !cast_any {
+            screw[turn] += 1;
+            "screw"
+        } else if leftover_mana >= 2 {
+            flood[turn] += 1;
+            "flood"
+        } else {
+            ok[turn] += 1;
+            "ok"
+        };
+
+        if record {
+            turn_records.push(TurnRecord {
+                turn,
+                drew,
+                land_played,
+                cards_cast: cast_log,
+                mana_produced,
+                mana_leftover: leftover_mana,
+                mana_spent,
+                classification: classification.to_string(),
+            });
+        }
+    }
+
+    let per_turn = screw.into_iter().zip(flood).zip(ok).map(|((s, f), o)| (s, f, o)).collect();
+
+    let replay = record.then(|| GameReplay {
+        deck_name: deck_name.to_string(),
+        seed,
+        strategy: strategy_kind,
+        keep_rule,
+        max_mulligans,
+        mulligans_taken,
+        deck_order,
+        turns: turn_records,
+    });
+
+    GameResult {
+        per_turn,
+        mulligans_taken,
+        cards_cast,
+        mana_produced: mana_produced_total,
+        mana_spent: mana_spent_total,
+        replay,
+    }
+}
+
+/// Re-runs a single recorded game deterministically from its seed, e.g. to drill
+/// into why it was classified screwed/flooded instead of only seeing averages.
+///
+/// `expected_deck_name` is the `deck_name` of the original recording; if
+/// `deck_file` doesn't match it, the two were never the same game and we
+/// refuse to silently reproduce a different one.
+#[allow(clippy::too_many_arguments)]
+pub fn replay_game(
+    deck_file: &DeckFile,
+    expected_deck_name: &str,
+    turns: usize,
+    seed: u64,
+    strategy: StrategyKind,
+    keep_rule: KeepRuleKind,
+    max_mulligans: usize,
+) -> Result<GameReplay, String> {
+    if deck_file.name != expected_deck_name {
+        return Err(format!(
+            "deck mismatch: replay was recorded against deck \"{}\", but the loaded deck is \"{}\"",
+            expected_deck_name, deck_file.name
+        ));
+    }
+
+    let deck = deck_file.expand();
+    let commander_card = deck_file.commander();
+    Ok(simulate_game(
+        &deck_file.name,
+        &deck,
+        &commander_card,
+        turns,
+        seed,
+        strategy,
+        keep_rule,
+        max_mulligans,
+        true,
+    )
+    .replay
+    .expect("simulate_game always returns a replay when record=true"))
+}
 
-                // Play cards, add power, toughness, creatures, etc stats counter to track play performance
-                let cast_any = play_cards(&mut hand, &mut mana, &mut commander);
+/// Runs `sims` simulations of `turns` turns each and aggregates per-turn stats.
+///
+/// `seed` is the base seed for the whole run; each simulated game derives its own
+/// RNG deterministically from it (`seed ^ sim_index`), so the aggregate is
+/// reproducible regardless of how `rayon` schedules the parallel tasks across
+/// threads, and any single game can be replayed in isolation from its own seed.
+///
+/// `strategy` selects the pilot policy used to decide which castable cards to
+/// play each turn; a fresh instance is built per game rather than shared across
+/// `rayon` tasks, since strategies may carry per-game state (see `CheatStrategy`).
+///
+/// When `replay_dir` is set, every game writes its own `GameReplay` JSON file
+/// there, named after its derived seed.
+///
+/// `keep_rule` and `max_mulligans` configure the London mulligan: a hand is
+/// mulliganed until `keep_rule` accepts it or `max_mulligans` is reached, and
+/// `Stats::mulligan_distribution` records how many games took each count.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    deck_file: &DeckFile,
+    sims: usize,
+    turns: usize,
+    seed: u64,
+    strategy: StrategyKind,
+    keep_rule: KeepRuleKind,
+    max_mulligans: usize,
+    replay_dir: Option<&Path>,
+) -> Stats {
+    let deck = deck_file.expand();
+    let commander_card = deck_file.commander();
 
-                // Calculate total leftover mana
-                let leftover_mana = mana.generic + mana.colors.values().sum::<u32>();
+    let results: Vec<GameResult> = (0..sims)
+        .into_par_iter()
+        .map(|sim_index| {
+            let game_seed = seed ^ sim_index as u64;
+            let result = simulate_game(
+                &deck_file.name,
+                &deck,
+                &commander_card,
+                turns,
+                game_seed,
+                strategy,
+                keep_rule,
+                max_mulligans,
+                replay_dir.is_some(),
+            );
 
-                // Classify turn
-                if !cast_any {
-                    screw[turn] += 1;
-                } else if leftover_mana >= 2 {
-                    flood[turn] += 1;
-                } else {
-                    ok[turn] += 1;
+            if let (Some(dir), Some(replay)) = (replay_dir, &result.replay) {
+                if let Err(e) = replay.write_to_dir(dir) {
+                    eprintln!("Failed to write replay for seed {}: {}", game_seed, e);
                 }
             }
 
-            screw.into_iter()
-                .zip(flood)
-                .zip(ok)
-                .map(|((s, f), o)| (s, f, o))
-                .collect()
+            result
         })
         .collect();
 
@@ -256,7 +370,7 @@ pub fn run(deck_file: &DeckFile, sims: usize, turns: usize) -> Stats {
         let mut f = 0;
         let mut o = 0;
         for r in &results {
-            let (rs, rf, ro) = r[t];
+            let (rs, rf, ro) = r.per_turn[t];
             s += rs;
             f += rf;
             o += ro;
@@ -267,5 +381,94 @@ pub fn run(deck_file: &DeckFile, sims: usize, turns: usize) -> Stats {
         ok[t] = o as f64 / n;
     }
 
-    Stats { screw, flood, ok }
+    let max_taken = results.iter().map(|r| r.mulligans_taken).max().unwrap_or(0);
+    let mut mulligan_distribution = vec![0.0; max_taken + 1];
+    for r in &results {
+        mulligan_distribution[r.mulligans_taken] += 1.0;
+    }
+    for count in &mut mulligan_distribution {
+        *count /= sims as f64;
+    }
+
+    let total_cards_cast: u64 = results.iter().map(|r| r.cards_cast as u64).sum();
+    let total_mana_produced: u64 = results.iter().map(|r| r.mana_produced).sum();
+    let total_mana_spent: u64 = results.iter().map(|r| r.mana_spent).sum();
+    let avg_cards_cast = total_cards_cast as f64 / sims as f64;
+    let mana_efficiency = if total_mana_produced > 0 {
+        total_mana_spent as f64 / total_mana_produced as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    Stats { screw, flood, ok, mulligan_distribution, avg_cards_cast, mana_efficiency }
+}
+
+/// Runs `sims` simulations of one deck and collapses them into a single
+/// comparison row (rather than `run`'s per-turn breakdown), so many decks can
+/// be run under the same seed/sim count and placed side by side in a table.
+#[allow(clippy::too_many_arguments)]
+pub fn summarize(
+    deck_file: &DeckFile,
+    name: String,
+    sims: usize,
+    turns: usize,
+    seed: u64,
+    strategy: StrategyKind,
+    keep_rule: KeepRuleKind,
+    max_mulligans: usize,
+) -> DeckSummary {
+    let deck = deck_file.expand();
+    let commander_card = deck_file.commander();
+
+    let results: Vec<GameResult> = (0..sims)
+        .into_par_iter()
+        .map(|sim_index| {
+            let game_seed = seed ^ sim_index as u64;
+            simulate_game(
+                &deck_file.name,
+                &deck,
+                &commander_card,
+                turns,
+                game_seed,
+                strategy,
+                keep_rule,
+                max_mulligans,
+                false,
+            )
+        })
+        .collect();
+
+    let mut screw = 0u64;
+    let mut flood = 0u64;
+    let mut ok = 0u64;
+    let mut cards_cast = 0u64;
+    let mut mana_produced = 0u64;
+    let mut mana_spent = 0u64;
+
+    for r in &results {
+        for &(s, f, o) in &r.per_turn {
+            screw += s as u64;
+            flood += f as u64;
+            ok += o as u64;
+        }
+        cards_cast += r.cards_cast as u64;
+        mana_produced += r.mana_produced;
+        mana_spent += r.mana_spent;
+    }
+
+    let turn_samples = (sims * turns) as f64;
+    let mana_efficiency = if mana_produced > 0 {
+        mana_spent as f64 / mana_produced as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    DeckSummary {
+        name,
+        screw_rate: screw as f64 / turn_samples * 100.0,
+        flood_rate: flood as f64 / turn_samples * 100.0,
+        ok_rate: ok as f64 / turn_samples * 100.0,
+        mana_efficiency,
+        avg_cards_cast: cards_cast as f64 / sims as f64,
+    }
 }
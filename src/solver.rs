@@ -0,0 +1,295 @@
+use crate::deck::Card;
+use crate::mana::ManaPool;
+use std::collections::{HashMap, VecDeque};
+
+const INF: i64 = i64::MAX / 4;
+
+struct Edge {
+    to: usize,
+    cap: i64,
+}
+
+/// Minimal Edmonds-Karp max-flow over a residual graph of paired forward/backward
+/// edges (edge `id` and its reverse always sit at consecutive indices, so the
+/// partner of edge `id` is `id ^ 1`).
+struct FlowNetwork {
+    edges: Vec<Edge>,
+    adj: Vec<Vec<usize>>,
+}
+
+impl FlowNetwork {
+    fn new(n: usize) -> Self {
+        Self { edges: Vec::new(), adj: vec![Vec::new(); n] }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, cap: i64) -> usize {
+        let id = self.edges.len();
+        self.edges.push(Edge { to, cap });
+        self.adj[from].push(id);
+        self.edges.push(Edge { to: from, cap: 0 });
+        self.adj[to].push(id + 1);
+        id
+    }
+
+    /// Flow actually pushed through a forward edge: it drains that edge's
+    /// capacity by exactly the amount it adds to its reverse edge's capacity.
+    fn flow_on(&self, forward_edge_id: usize) -> i64 {
+        self.edges[forward_edge_id ^ 1].cap
+    }
+
+    fn max_flow(&mut self, source: usize, sink: usize) -> i64 {
+        let mut total = 0;
+
+        loop {
+            let mut parent_edge = vec![usize::MAX; self.adj.len()];
+            let mut visited = vec![false; self.adj.len()];
+            visited[source] = true;
+            let mut queue = VecDeque::new();
+            queue.push_back(source);
+
+            while let Some(u) = queue.pop_front() {
+                if u == sink {
+                    break;
+                }
+                for &eid in &self.adj[u] {
+                    let e = &self.edges[eid];
+                    if e.cap > 0 && !visited[e.to] {
+                        visited[e.to] = true;
+                        parent_edge[e.to] = eid;
+                        queue.push_back(e.to);
+                    }
+                }
+            }
+
+            if !visited[sink] {
+                break;
+            }
+
+            let mut bottleneck = INF;
+            let mut v = sink;
+            while v != source {
+                let eid = parent_edge[v];
+                bottleneck = bottleneck.min(self.edges[eid].cap);
+                v = self.edges[eid ^ 1].to;
+            }
+
+            let mut v = sink;
+            while v != source {
+                let eid = parent_edge[v];
+                self.edges[eid].cap -= bottleneck;
+                self.edges[eid ^ 1].cap += bottleneck;
+                v = self.edges[eid ^ 1].to;
+            }
+
+            total += bottleneck;
+        }
+
+        total
+    }
+}
+
+/// A feasible joint cast of a candidate set: which hand indices are castable
+/// together, and the exact mana spent to pay for them.
+pub struct Allocation {
+    pub cast: Vec<usize>,
+    pub colored_spend: HashMap<String, u32>,
+    pub generic_spend: u32,
+}
+
+fn cost_of(card: &Card) -> (u32, &[String]) {
+    match card {
+        Card::Spell { generic, pips, .. } => (*generic as u32, pips.as_slice()),
+        Card::Commander { generic, pips, .. } => (*generic as u32, pips.as_slice()),
+        Card::Ramp { generic, .. } => (*generic as u32, &[]),
+        Card::Fetch { generic, .. } => (*generic as u32, &[]),
+        Card::Land { .. } => (0, &[]),
+    }
+}
+
+/// Checks whether `candidates` (hand indices) are jointly castable from `mana`,
+/// and if so the exact mana assignment, by modeling payment as max-flow over a
+/// bipartite graph: a source feeds one node per color (capacity = mana
+/// available in that color) plus a generic node. Each required colored pip is a
+/// demand node reachable only from its matching color; every candidate's
+/// generic cost instead feeds one shared generic-demand node reachable from
+/// every color's leftover and from the generic node. The set is payable as a
+/// whole iff every demand unit ends up saturated.
+fn solve(mana: &ManaPool, hand: &[Card], candidates: &[usize]) -> Option<Allocation> {
+    let mut color_node: HashMap<String, usize> = HashMap::new();
+    let mut node_count = 1; // 0 is the source
+    for c in mana.colors.keys() {
+        color_node.insert(c.clone(), node_count);
+        node_count += 1;
+    }
+    let generic_supply = node_count;
+    node_count += 1;
+    let generic_demand = node_count;
+    node_count += 1;
+
+    let mut pip_count: HashMap<(usize, String), u32> = HashMap::new();
+    let mut total_generic_cost: u32 = 0;
+    for &idx in candidates {
+        let (generic_cost, pips) = cost_of(&hand[idx]);
+        total_generic_cost += generic_cost;
+        for p in pips {
+            *pip_count.entry((idx, p.clone())).or_insert(0) += 1;
+        }
+    }
+
+    let mut pip_demand_node: HashMap<(usize, String), usize> = HashMap::new();
+    for key in pip_count.keys() {
+        pip_demand_node.insert(key.clone(), node_count);
+        node_count += 1;
+    }
+
+    let sink = node_count;
+    node_count += 1;
+
+    let mut net = FlowNetwork::new(node_count);
+
+    let mut color_supply_edge: HashMap<String, usize> = HashMap::new();
+    for (c, &node) in &color_node {
+        let cap = *mana.colors.get(c).unwrap_or(&0) as i64;
+        let eid = net.add_edge(0, node, cap);
+        color_supply_edge.insert(c.clone(), eid);
+    }
+    let generic_supply_edge = net.add_edge(0, generic_supply, mana.generic as i64);
+
+    for (&(idx, ref color), &count) in &pip_count {
+        let &color_n = color_node.get(color)?;
+        let demand_n = pip_demand_node[&(idx, color.clone())];
+        net.add_edge(color_n, demand_n, count as i64);
+        net.add_edge(demand_n, sink, count as i64);
+    }
+
+    for &node in color_node.values() {
+        net.add_edge(node, generic_demand, INF);
+    }
+    net.add_edge(generic_supply, generic_demand, INF);
+    net.add_edge(generic_demand, sink, total_generic_cost as i64);
+
+    let total_demand: i64 =
+        pip_count.values().map(|&c| c as i64).sum::<i64>() + total_generic_cost as i64;
+
+    if net.max_flow(0, sink) < total_demand {
+        return None;
+    }
+
+    let colored_spend = color_supply_edge
+        .into_iter()
+        .map(|(c, eid)| (c, net.flow_on(eid) as u32))
+        .collect();
+    let generic_spend = net.flow_on(generic_supply_edge) as u32;
+
+    Some(Allocation { cast: candidates.to_vec(), colored_spend, generic_spend })
+}
+
+/// Greedily grows a committed set of candidates (hand indices, tried in the
+/// given order) for as long as the enlarged set stays jointly payable, so a
+/// card that would strand a color a later candidate needs is skipped rather
+/// than cast first-come-first-served. This is order-dependent: committing a
+/// multi-color card first can block two single-color cards that would
+/// otherwise both fit (e.g. pool `{1 W, 1 U}` with candidates `{W,U}`, `{W}`,
+/// `{U}` casts only the first if it's tried first, but both singles if tried
+/// before it). Returns the largest committed allocation found for `order`, or
+/// an empty one if nothing in `order` is affordable.
+fn greedy_grow(mana: &ManaPool, hand: &[Card], order: &[usize]) -> Allocation {
+    let mut committed: Vec<usize> = Vec::new();
+    let mut best: Option<Allocation> = None;
+
+    for &idx in order {
+        committed.push(idx);
+        match solve(mana, hand, &committed) {
+            Some(alloc) => best = Some(alloc),
+            None => {
+                committed.pop();
+            }
+        }
+    }
+
+    best.unwrap_or_else(|| Allocation {
+        cast: Vec::new(),
+        colored_spend: HashMap::new(),
+        generic_spend: 0,
+    })
+}
+
+/// Finds a jointly-castable set of `candidates` (hand indices) from `mana`.
+/// `greedy_grow` is order-dependent, so rather than trust the single priority
+/// order a caller passes in, this tries it alongside an ascending-cost order
+/// and a most-constrained-first order (most colored pips first, since those
+/// are the candidates most likely to strand a color) and keeps whichever
+/// committed the most cards. This is still a heuristic, not an exhaustive
+/// search over all `2^n` subsets, so it is not guaranteed to find the true
+/// maximum jointly-castable set — just a better approximation than any single
+/// fixed order.
+pub fn allocate(mana: &ManaPool, hand: &[Card], order: &[usize]) -> Allocation {
+    let mut ascending_cost = order.to_vec();
+    ascending_cost.sort_by_key(|&idx| {
+        let (generic, pips) = cost_of(&hand[idx]);
+        generic + pips.len() as u32
+    });
+
+    let mut most_constrained_first = order.to_vec();
+    most_constrained_first.sort_by_key(|&idx| std::cmp::Reverse(cost_of(&hand[idx]).1.len()));
+
+    [order.to_vec(), ascending_cost, most_constrained_first]
+        .into_iter()
+        .map(|candidate_order| greedy_grow(mana, hand, &candidate_order))
+        .max_by_key(|alloc| alloc.cast.len())
+        .expect("exactly three orderings are always tried")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spell(generic: u8, pips: &[&str]) -> Card {
+        Card::Spell { generic, pips: pips.iter().map(|s| s.to_string()).collect(), count: 1 }
+    }
+
+    fn pool(colors: &[(&str, u32)]) -> ManaPool {
+        let mut mana = ManaPool::new();
+        for &(c, n) in colors {
+            mana.add_color(c, n);
+        }
+        mana
+    }
+
+    #[test]
+    fn joint_color_contention_prefers_the_larger_set() {
+        let mana = pool(&[("W", 1), ("U", 1)]);
+        let hand = [spell(0, &["W", "U"]), spell(0, &["W"]), spell(0, &["U"])];
+
+        // Priority order commits the two-color card first, which would strand
+        // both single-color cards if `allocate` trusted it blindly.
+        let alloc = allocate(&mana, &hand, &[0, 1, 2]);
+
+        assert_eq!(alloc.cast.len(), 2);
+        let mut cast = alloc.cast.clone();
+        cast.sort_unstable();
+        assert_eq!(cast, vec![1, 2]);
+    }
+
+    #[test]
+    fn generic_cost_can_be_paid_from_leftover_color_mana() {
+        let mana = pool(&[("W", 2)]);
+        let hand = [spell(1, &["W"])];
+
+        let alloc = allocate(&mana, &hand, &[0]);
+
+        assert_eq!(alloc.cast, vec![0]);
+        assert_eq!(alloc.generic_spend, 0);
+        assert_eq!(alloc.colored_spend.get("W").copied().unwrap_or(0), 2);
+    }
+
+    #[test]
+    fn infeasible_demand_casts_nothing() {
+        let mana = pool(&[]);
+        let hand = [spell(1, &[])];
+
+        let alloc = allocate(&mana, &hand, &[0]);
+
+        assert!(alloc.cast.is_empty());
+    }
+}
@@ -9,6 +9,13 @@ pub struct Stats {
     pub flood: Vec<f64>,
     #[pyo3(get)]
     pub ok: Vec<f64>,
+    /// Fraction of games that took exactly `i` mulligans, indexed by `i`.
+    #[pyo3(get)]
+    pub mulligan_distribution: Vec<f64>,
+    #[pyo3(get)]
+    pub avg_cards_cast: f64,
+    #[pyo3(get)]
+    pub mana_efficiency: f64,
 }
 
 impl Stats {
@@ -17,7 +24,29 @@ impl Stats {
             screw: vec![0.0; n],
             flood: vec![0.0; n],
             ok: vec![0.0; n],
+            mulligan_distribution: Vec::new(),
+            avg_cards_cast: 0.0,
+            mana_efficiency: 0.0,
         }
     }
 }
 
+/// Aggregate rates for one deck under a shared seed/sim count/turn count,
+/// one row of a multi-deck comparison table.
+#[pyclass]
+#[derive(Clone)]
+pub struct DeckSummary {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub screw_rate: f64,
+    #[pyo3(get)]
+    pub flood_rate: f64,
+    #[pyo3(get)]
+    pub ok_rate: f64,
+    #[pyo3(get)]
+    pub mana_efficiency: f64,
+    #[pyo3(get)]
+    pub avg_cards_cast: f64,
+}
+
@@ -0,0 +1,126 @@
+use crate::deck::Card;
+use crate::mana::ManaPool;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+/// Picks which cards in hand to attempt casting, and in what order, each time
+/// `play_cards` loops. Separating this from mana availability lets pilot skill
+/// and deck quality be measured independently.
+pub trait CastStrategy {
+    fn choose_plays(&self, hand: &[Card], mana: &ManaPool, commander: &Option<Card>) -> Vec<usize>;
+
+    /// Called once per turn with the card that will be drawn next turn, before
+    /// `choose_plays`. Only `CheatStrategy` makes use of it; the default is a no-op.
+    fn peek(&self, _next_draw: Option<&Card>) {}
+}
+
+/// The original heuristic: ramp/fetch first (they produce mana), then spells,
+/// both in reverse hand order.
+pub struct GreedyStrategy;
+
+impl CastStrategy for GreedyStrategy {
+    fn choose_plays(&self, hand: &[Card], _mana: &ManaPool, _commander: &Option<Card>) -> Vec<usize> {
+        let mut order = Vec::new();
+        for i in (0..hand.len()).rev() {
+            if matches!(hand[i], Card::Ramp { .. } | Card::Fetch { .. }) {
+                order.push(i);
+            }
+        }
+        for i in (0..hand.len()).rev() {
+            if matches!(hand[i], Card::Spell { .. }) {
+                order.push(i);
+            }
+        }
+        order
+    }
+}
+
+/// Always tries the highest mana-value card in hand first, regardless of type.
+pub struct CurveFillerStrategy;
+
+impl CastStrategy for CurveFillerStrategy {
+    fn choose_plays(&self, hand: &[Card], _mana: &ManaPool, _commander: &Option<Card>) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..hand.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(mana_value(&hand[i])));
+        order
+    }
+}
+
+/// Sees the top of the draw deck before acting. When next turn's draw
+/// out-values everything currently in hand, it prioritizes ramp/fetch this
+/// turn to stretch toward affording that card; otherwise it curve-fills like
+/// `CurveFillerStrategy`.
+pub struct CheatStrategy {
+    next_draw: RefCell<Option<Card>>,
+}
+
+impl CheatStrategy {
+    pub fn new() -> Self {
+        Self { next_draw: RefCell::new(None) }
+    }
+}
+
+impl Default for CheatStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CastStrategy for CheatStrategy {
+    fn peek(&self, next_draw: Option<&Card>) {
+        *self.next_draw.borrow_mut() = next_draw.cloned();
+    }
+
+    fn choose_plays(&self, hand: &[Card], _mana: &ManaPool, _commander: &Option<Card>) -> Vec<usize> {
+        let peeked_value = self.next_draw.borrow().as_ref().map(mana_value);
+        let current_best =
+            hand.iter().filter(|c| !matches!(c, Card::Land { .. })).map(mana_value).max().unwrap_or(0);
+
+        let mut order: Vec<usize> = (0..hand.len()).collect();
+
+        if peeked_value.is_some_and(|v| v > current_best) {
+            order.sort_by_key(|&i| {
+                let is_ramp = matches!(hand[i], Card::Ramp { .. } | Card::Fetch { .. });
+                (!is_ramp, std::cmp::Reverse(mana_value(&hand[i])))
+            });
+        } else {
+            order.sort_by_key(|&i| std::cmp::Reverse(mana_value(&hand[i])));
+        }
+
+        order
+    }
+}
+
+pub(crate) fn mana_value(card: &Card) -> u32 {
+    match card {
+        Card::Spell { generic, pips, .. } => *generic as u32 + pips.len() as u32,
+        Card::Ramp { generic, .. } => *generic as u32,
+        Card::Fetch { generic, .. } => *generic as u32,
+        Card::Commander { generic, pips, .. } => *generic as u32 + pips.len() as u32,
+        Card::Land { .. } => 0,
+    }
+}
+
+/// Which `CastStrategy` to run a simulation with, selected via `-g/--strategy`.
+/// Also recorded in replays so `--replay` can rebuild the same pilot policy.
+#[derive(Clone, Copy, Debug, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrategyKind {
+    Greedy,
+    Curve,
+    Cheat,
+}
+
+impl StrategyKind {
+    /// Builds a fresh strategy instance. Called once per simulated game rather
+    /// than shared across `rayon` tasks, since `CheatStrategy` carries per-game
+    /// peek state.
+    pub fn build(self) -> Box<dyn CastStrategy> {
+        match self {
+            StrategyKind::Greedy => Box::new(GreedyStrategy),
+            StrategyKind::Curve => Box::new(CurveFillerStrategy),
+            StrategyKind::Cheat => Box::new(CheatStrategy::new()),
+        }
+    }
+}